@@ -1,86 +1,384 @@
 use num::Complex;
 use std::str::FromStr;
-use image::{RgbImage, Rgb};
-use std::env;
+use image::{RgbImage, Rgb, Pixel, ImageBuffer};
+use std::sync::atomic::{AtomicU32, Ordering};
+use rand::Rng;
 use rayon::prelude::*;
+use clap::Parser;
 
-const ELEMENT_BYTES:usize = 3;
+const BUDDHABROT_SAMPLES: usize = 2_000_000;
+
+/// Render the Mandelbrot set and related escape-time fractals.
+#[derive(Parser, Debug)]
+#[command(author, version, about)]
+struct Args {
+    /// Output image path. A '.ppm'/'.pnm' extension writes a raw P6 PPM;
+    /// any other extension (e.g. '.png') picks the image crate's encoder.
+    output: String,
+
+    /// Image dimensions as WIDTHxHEIGHT, e.g. 1000x750
+    #[arg(long, default_value = "1000x750")]
+    size: String,
+
+    /// Upper-left corner of the viewport, re,im (pair with --lower-right, or
+    /// use --center/--zoom instead), e.g. --upper-left -2,1
+    #[arg(long, allow_hyphen_values = true)]
+    upper_left: Option<String>,
+
+    /// Lower-right corner of the viewport, re,im, e.g. --lower-right 1,-1
+    #[arg(long, allow_hyphen_values = true)]
+    lower_right: Option<String>,
+
+    /// Center point of the viewport, re,im (pair with --zoom instead of
+    /// --upper-left/--lower-right)
+    #[arg(long, allow_hyphen_values = true)]
+    center: Option<String>,
+
+    /// Width of the viewport in the complex plane, for use with --center
+    #[arg(long)]
+    zoom: Option<f64>,
+
+    /// Rendering mode: single, multi, or buddhabrot
+    #[arg(long, default_value = "multi")]
+    mode: RenderMode,
+
+    /// Fractal kind: mandelbrot, multibrot:<power>, burningship, or julia:<re>,<im>
+    #[arg(long, default_value = "mandelbrot")]
+    fractal: FractalKind,
+
+    /// Number of Rayon worker threads to use for --mode multi and --mode buddhabrot
+    /// (defaults to all cores)
+    #[arg(long)]
+    threads: Option<usize>,
+
+    /// Escape-time iteration limit
+    #[arg(long, default_value_t = 255)]
+    iteration_limit: usize,
+
+    /// Sample count for --mode buddhabrot
+    #[arg(long, default_value_t = BUDDHABROT_SAMPLES)]
+    samples: usize,
+}
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
+    let args = Args::parse();
 
-    if args.len() != 6 {
-        eprint!("Usage: {}, FILE, PIXELS, UPPERLEFT, LOWERRIGHT MULTI ", args[0]);
-        eprintln!("Example: {} mandel.png 1000x750 -1.20, 0.35, -1, 0.20, Multi", args[0]);
-        std::process::exit(1);
+    let bounds: (u32, u32) = parse_pair(&args.size, 'x')
+        .expect("error parsing --size");
+
+    if let Some(threads) = args.threads {
+        rayon::ThreadPoolBuilder::new().num_threads(threads).build_global()
+            .expect("error configuring thread pool");
     }
 
-    let bounds:(u32,u32) = parse_pair(&args[2], 'x')
-        .expect("error parsing image dimensions");
-    let upper_left = parse_complex(&args[3])
-        .expect("error parsing upper left corner point");
-    let lower_right = parse_complex(&args[4])
-        .expect("error parsing lower right corner point");
-    let multi = parse_multi(&args[5])
-        .expect("error parsing multi argument - can only be 'Single' or 'Multi'");
+    let (upper_left, lower_right) = resolve_viewport(&args, bounds)
+        .expect("pass --upper-left/--lower-right or --center/--zoom to describe the viewport");
 
     let mut pixels = RgbImage::new(bounds.0, bounds.1);
 
-    match multi {
-        true => render_multi(&mut pixels, upper_left, lower_right), 
-        false => render_single(&mut pixels, upper_left, lower_right)
-    }    
+    match args.mode {
+        RenderMode::Single =>
+            render_single(&mut pixels, upper_left, lower_right, args.fractal, args.iteration_limit),
+        RenderMode::Multi =>
+            render_multi(&mut pixels, upper_left, lower_right, args.fractal, args.iteration_limit),
+        RenderMode::Buddhabrot =>
+            render_buddhabrot(&mut pixels, upper_left, lower_right, args.samples, args.iteration_limit),
+    }
+
+    write_image(&args.output, &pixels)
+        .expect("error writing output image");
+}
+
+/// Resolve the viewport from either an explicit --upper-left/--lower-right
+/// pair or a --center/--zoom pair, matching 'bounds' aspect ratio for the
+/// latter. Returns 'None' if neither pair was fully specified.
+fn resolve_viewport(args: &Args, bounds: (u32, u32)) -> Option<(Complex<f64>, Complex<f64>)> {
+    if let (Some(upper_left), Some(lower_right)) = (&args.upper_left, &args.lower_right) {
+        return Some((parse_complex(upper_left)?, parse_complex(lower_right)?));
+    }
+
+    if let (Some(center), Some(zoom)) = (&args.center, args.zoom) {
+        let center = parse_complex(center)?;
+        let aspect = bounds.1 as f64 / bounds.0 as f64;
+        let half_width = zoom / 2.0;
+        let half_height = half_width * aspect;
+        return Some((
+            Complex { re: center.re - half_width, im: center.im + half_height },
+            Complex { re: center.re + half_width, im: center.im - half_height },
+        ));
+    }
+
+    None
+}
+
+#[cfg(test)]
+fn test_args(output: &str) -> Args {
+    Args {
+        output: output.to_string(),
+        size: "100x100".to_string(),
+        upper_left: None,
+        lower_right: None,
+        center: None,
+        zoom: None,
+        mode: RenderMode::Multi,
+        fractal: FractalKind::Mandelbrot,
+        threads: None,
+        iteration_limit: 255,
+        samples: BUDDHABROT_SAMPLES,
+    }
+}
+
+#[test]
+fn test_resolve_viewport_corners() {
+    let args = Args {
+        upper_left: Some("-1,1".to_string()),
+        lower_right: Some("1,-1".to_string()),
+        ..test_args("out.png")
+    };
+    assert_eq!(resolve_viewport(&args, (100, 100)),
+        Some((Complex { re: -1.0, im: 1.0 }, Complex { re: 1.0, im: -1.0 })));
+}
+
+#[test]
+fn test_resolve_viewport_center_zoom() {
+    let args = Args {
+        center: Some("0,0".to_string()),
+        zoom: Some(2.0),
+        ..test_args("out.png")
+    };
+    assert_eq!(resolve_viewport(&args, (100, 100)),
+        Some((Complex { re: -1.0, im: 1.0 }, Complex { re: 1.0, im: -1.0 })));
+}
+
+#[test]
+fn test_resolve_viewport_none() {
+    assert_eq!(resolve_viewport(&test_args("out.png"), (100, 100)), None);
+}
+
+#[test]
+fn test_args_parse_negative_coordinates() {
+    // The classic full-set view has a negative upper-left and lower-right,
+    // so clap must accept '-2,1' as a value rather than mistaking it for a
+    // flag; see allow_hyphen_values on upper_left/lower_right/center.
+    let args = Args::parse_from([
+        "mandelbrot", "out.png", "--size", "60x40",
+        "--upper-left", "-2,1", "--lower-right", "1,-1", "--mode", "single",
+    ]);
+    assert_eq!(args.upper_left.as_deref(), Some("-2,1"));
+    assert_eq!(args.lower_right.as_deref(), Some("1,-1"));
+
+    let args = Args::parse_from([
+        "mandelbrot", "out.png", "--center", "-0.5,0", "--zoom", "2.0",
+    ]);
+    assert_eq!(args.center.as_deref(), Some("-0.5,0"));
+}
+
+/// Write 'pixels' to 'path'. A '.ppm'/'.pnm' extension (case-insensitive)
+/// writes a raw P6 PPM directly; anything else goes through the 'image'
+/// crate, which chooses the encoder from the extension (PNG for '.png') and
+/// returns an error for an extension it does not recognize.
+fn write_image(path: &str, pixels: &RgbImage) -> std::io::Result<()> {
+    let is_ppm = path.rsplit('.').next()
+        .map(|ext| ext.eq_ignore_ascii_case("ppm") || ext.eq_ignore_ascii_case("pnm"))
+        .unwrap_or(false);
+
+    if is_ppm {
+        write_ppm(path, pixels)
+    } else {
+        pixels.save(path).map_err(std::io::Error::other)
+    }
+}
+
+/// Write 'pixels' as a raw P6 PPM (binary, 8 bits per channel).
+fn write_ppm(path: &str, pixels: &RgbImage) -> std::io::Result<()> {
+    use std::io::Write;
+    let mut file = std::fs::File::create(path)?;
+    write!(file, "P6\n{} {}\n255\n", pixels.width(), pixels.height())?;
+    file.write_all(pixels.as_raw())
+}
 
-    pixels.save(&args[1])
-        .expect("error writing png file");
+#[test]
+fn test_write_ppm() {
+    let pixels = RgbImage::from_pixel(2, 1, Rgb([10, 20, 30]));
+    let path = std::env::temp_dir().join("mandelbrot_test_write_ppm.ppm");
+
+    write_ppm(path.to_str().unwrap(), &pixels).expect("write_ppm failed");
+    let bytes = std::fs::read(&path).expect("failed to read back the PPM");
+    std::fs::remove_file(&path).ok();
+
+    let mut expected = b"P6\n2 1\n255\n".to_vec();
+    expected.extend_from_slice(&[10, 20, 30, 10, 20, 30]);
+    assert_eq!(bytes, expected);
+}
+
+/// The three ways `main` can turn a viewport into pixels: iterate each pixel
+/// in turn, iterate pixels concurrently via Rayon, or accumulate orbit
+/// density for a Buddhabrot.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum RenderMode {
+    Single,
+    Multi,
+    Buddhabrot,
 }
 
-fn parse_multi(arg: &str) -> Option<bool> {
-    match arg {
-        "Multi" => Some(true),
-        "Single" => Some(false),
-        _ => None
+impl FromStr for RenderMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "single" => Ok(RenderMode::Single),
+            "multi" => Ok(RenderMode::Multi),
+            "buddhabrot" => Ok(RenderMode::Buddhabrot),
+            other => Err(format!("unknown mode: {} (expected single, multi, or buddhabrot)", other)),
+        }
     }
 }
 
 #[test]
-fn test_parse_multi()
+fn test_render_mode_from_str()
 {
-    assert_eq!(parse_multi("Single"), Some(false));
-    assert_eq!(parse_multi("Multi"), Some(true));
-    assert_eq!(parse_multi("FooBar"), None);
-    assert_eq!(parse_multi(""), None);
-
-}
-
-/// Try to determine if 'c' is in the Mandelbrot set, using at most 'limit'
-/// iterations to decide.
-/// 
-/// If 'c' is not a member, return 'Some(i)' where 'i' is the number of 
-/// iterations it took for 'c' to leave the circle of radius 2 centered on the 
-/// origin. If 'c' seems to be a member (more precisely, if we reached the 
-/// iteration limit without being able to prove that 'c' is not a member),
-/// return None.
-fn escape_time(c: Complex<f64>, limit: usize) -> Option<usize> {
-    
-    let mut z = Complex {re: 0.0, im: 0.0 };
+    assert_eq!(RenderMode::from_str("single"), Ok(RenderMode::Single));
+    assert_eq!(RenderMode::from_str("multi"), Ok(RenderMode::Multi));
+    assert_eq!(RenderMode::from_str("buddhabrot"), Ok(RenderMode::Buddhabrot));
+    assert!(RenderMode::from_str("FooBar").is_err());
+    assert!(RenderMode::from_str("").is_err());
+}
+
+/// The family of escape-time fractals `escape_time` knows how to render.
+///
+/// Parsed from a CLI argument via `FromStr`: `"mandelbrot"`, `"multibrot:<power>"`
+/// (power defaults to 2, same as Mandelbrot), `"burningship"`, or
+/// `"julia:<re>,<im>"`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum FractalKind {
+    Mandelbrot,
+    Multibrot { power: i32 },
+    BurningShip,
+    Julia { c: Complex<f64> },
+}
+
+impl FromStr for FractalKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.splitn(2, ':');
+        match parts.next().unwrap_or("") {
+            "mandelbrot" => Ok(FractalKind::Mandelbrot),
+            "multibrot" => {
+                let power = match parts.next() {
+                    Some(p) => p.parse::<i32>()
+                        .map_err(|_| format!("invalid multibrot power: {}", p))?,
+                    None => 2,
+                };
+                if power < 2 {
+                    // escape_time's smoothing formula divides by ln(power),
+                    // which is 0 (or undefined) below power 2.
+                    return Err(format!("multibrot power must be at least 2, got {}", power));
+                }
+                Ok(FractalKind::Multibrot { power })
+            }
+            "burningship" => Ok(FractalKind::BurningShip),
+            "julia" => {
+                let c = match parts.next() {
+                    Some(p) => parse_complex(p)
+                        .ok_or_else(|| format!("invalid julia constant: {}", p))?,
+                    None => return Err("julia requires a constant, e.g. julia:-0.8,0.156".to_string()),
+                };
+                Ok(FractalKind::Julia { c })
+            }
+            other => Err(format!("unknown fractal kind: {}", other)),
+        }
+    }
+}
+
+#[test]
+fn test_fractal_kind_from_str() {
+    assert_eq!(FractalKind::from_str("mandelbrot"), Ok(FractalKind::Mandelbrot));
+    assert_eq!(FractalKind::from_str("multibrot"), Ok(FractalKind::Multibrot { power: 2 }));
+    assert_eq!(FractalKind::from_str("multibrot:5"), Ok(FractalKind::Multibrot { power: 5 }));
+    assert_eq!(FractalKind::from_str("burningship"), Ok(FractalKind::BurningShip));
+    assert_eq!(FractalKind::from_str("julia:-0.8,0.156"),
+        Ok(FractalKind::Julia { c: Complex { re: -0.8, im: 0.156 } }));
+    assert!(FractalKind::from_str("julia").is_err());
+    assert!(FractalKind::from_str("nonsense").is_err());
+    assert!(FractalKind::from_str("multibrot:1").is_err());
+    assert!(FractalKind::from_str("multibrot:0").is_err());
+    assert!(FractalKind::from_str("multibrot:-1").is_err());
+}
+
+/// Try to determine if 'c' is in the set described by 'kind', using at most
+/// 'limit' iterations to decide.
+///
+/// Unlike a plain iteration count, this returns a continuous "normalized
+/// iteration count" `mu` on escape, which removes the harsh contour bands a
+/// discrete count produces when fed to a color gradient. The bailout radius
+/// is widened to 256 (so `norm_sqr() > 65536.0`), and to 10^6 for a
+/// Multibrot of power above 3, because the smoothing formula needs `z` to be
+/// well clear of the set boundary to be accurate.
+///
+/// If 'c' is not a member, return 'Some(mu)'. If 'c' seems to be a member
+/// (more precisely, if we reached the iteration limit without being able to
+/// prove that 'c' is not a member), return None.
+fn escape_time(c: Complex<f64>, limit: usize, kind: FractalKind) -> Option<f64> {
+
+    let mut z = match kind {
+        FractalKind::Julia { .. } => c,
+        _ => Complex { re: 0.0, im: 0.0 },
+    };
+    let c = match kind {
+        FractalKind::Julia { c } => c,
+        _ => c,
+    };
+    let bailout = match kind {
+        // Higher powers escape much further out, so even the widened
+        // radius-256 bailout would clip the set; widen it further still.
+        FractalKind::Multibrot { power } if power > 3 => 1.0e12,
+        _ => 65536.0,
+    };
+    // The smoothing formula's log base must match the recurrence's power -
+    // 2 for everything here except an explicit higher-power Multibrot.
+    let power = match kind {
+        FractalKind::Multibrot { power } => power as f64,
+        _ => 2.0,
+    };
+
     for i in 0..limit {
-        if z.norm_sqr() > 4.0 {
-            return Some(i);
+        if z.norm_sqr() > bailout {
+            let mu = i as f64 + 1.0 - (z.norm().ln().ln() / power.ln());
+            return Some(mu);
         }
-        z = z * z + c;
+        z = match kind {
+            FractalKind::Mandelbrot | FractalKind::Julia { .. } => z * z + c,
+            FractalKind::Multibrot { power } => z.powi(power) + c,
+            FractalKind::BurningShip => {
+                let folded = Complex { re: z.re.abs(), im: z.im.abs() };
+                folded * folded + c
+            }
+        };
     }
 
     None
 }
 
+#[test]
+fn test_escape_time_smooth() {
+    // Far outside the bailout circle, the orbit escapes on the first step.
+    let far = Complex { re: 1000.0, im: 0.0 };
+    let mu = escape_time(far, 50, FractalKind::Mandelbrot);
+    assert!(matches!(mu, Some(m) if m < 1.0));
+
+    // The origin is a fixed point of z^2 + 0, so it never escapes.
+    let origin = Complex { re: 0.0, im: 0.0 };
+    assert_eq!(escape_time(origin, 50, FractalKind::Mandelbrot), None);
+}
+
 /// Parse the string 's' as a coordinate pair, like '"400x600" or '"1.0,0.5"'.
-/// 
+///
 /// Specifically, 's' should have the form <left><separator><right> where <sep> is
 /// the character given by the 'separator' argument, and <left>, and <right> are
 /// both strings that can be parsed by 'T::from_str'. 'separator' must be an
 /// ASCII character.
-/// 
+///
 /// if 's' has the proper form, return 'Some<(x,y)>'. If it doesn't parse
 /// correctly, return 'None'.
 fn parse_pair<T: FromStr>(s: &str, separator:char) -> Option<(T,T)> {
@@ -109,7 +407,7 @@ fn test_parse_pair() {
 fn parse_complex(s: &str) -> Option<Complex<f64>> {
     match parse_pair(s, ',') {
         Some((re,im)) => Some(Complex {re, im}),
-        None => None    
+        None => None
     }
 }
 
@@ -121,7 +419,7 @@ fn test_parse_complex() {
 
 /// Given the row and column of a pixel in the output image, return the
 /// corresponding point on the complex plane.
-/// 
+///
 /// 'bounds' is a pair giving the width and height of the image in pixels.
 /// 'pixel' is a (column, row) pair indicating a particular pixel in that image.
 /// The 'upper_left' and 'lower_right' parameters are points on the complex
@@ -133,67 +431,102 @@ fn pixel_to_point(bounds: (usize,usize),
 {
     let (width, height) = (lower_right.re - upper_left.re,
                                      upper_left.im - lower_right.im);
-    Complex { 
-        re: upper_left.re + pixel.0 as f64 * width / bounds.0 as f64, 
-        im: upper_left.im - pixel.1 as f64 * height / bounds.1 as f64 
+    Complex {
+        re: upper_left.re + pixel.0 as f64 * width / bounds.0 as f64,
+        im: upper_left.im - pixel.1 as f64 * height / bounds.1 as f64
     }
 }
 
 #[test]
 fn test_pixel_to_point() {
-    assert_eq!(pixel_to_point((100,200), (25,175), 
-            Complex{ re: -1.0, im: 1.0}, 
-            Complex {re: 1.0, im: -1.0}), 
+    assert_eq!(pixel_to_point((100,200), (25,175),
+            Complex{ re: -1.0, im: 1.0},
+            Complex {re: 1.0, im: -1.0}),
         Complex { re: -0.5, im:-0.75});
 }
 
+/// The inverse of 'pixel_to_point': given a point on the complex plane,
+/// return the (column, row) of the pixel it falls in, or 'None' if the point
+/// lies outside the viewport described by 'upper_left' and 'lower_right'.
+fn point_to_pixel(bounds: (usize, usize),
+                   point: Complex<f64>,
+                   upper_left: Complex<f64>,
+                   lower_right: Complex<f64>) -> Option<(usize, usize)>
+{
+    let (width, height) = (lower_right.re - upper_left.re,
+                                     upper_left.im - lower_right.im);
+    let column = (point.re - upper_left.re) / width * bounds.0 as f64;
+    let row = (upper_left.im - point.im) / height * bounds.1 as f64;
+
+    if column < 0.0 || row < 0.0 || column >= bounds.0 as f64 || row >= bounds.1 as f64 {
+        return None;
+    }
+
+    Some((column as usize, row as usize))
+}
+
+#[test]
+fn test_point_to_pixel() {
+    assert_eq!(point_to_pixel((100,200),
+            Complex { re: -0.5, im: -0.75 },
+            Complex { re: -1.0, im: 1.0 },
+            Complex { re: 1.0, im: -1.0 }),
+        Some((25,175)));
+    assert_eq!(point_to_pixel((100,200),
+            Complex { re: -5.0, im: -0.75 },
+            Complex { re: -1.0, im: 1.0 },
+            Complex { re: 1.0, im: -1.0 }),
+        None);
+}
+
 /// Render a rectangle of the Mandelbrot set into a buffer of pixels.
-/// 
+///
 /// The 'bounds' argument gives the width and height fo the buffer. 'pixels',
 /// which holds one grayscale pixel per byte. The 'upper_left' and 'lower_right'
 /// arguments specify points on the complex plane corresponding to the upper-left
 ///  and lower-right corners of the pixel buffer
-fn render_single(pixels:&mut RgbImage,
+fn render_single<P>(pixels: &mut ImageBuffer<P, Vec<u8>>,
           upper_left: Complex<f64>,
-          lower_right: Complex<f64>)
+          lower_right: Complex<f64>,
+          kind: FractalKind,
+          limit: usize)
+where
+    P: Pixel<Subpixel = u8>,
 {
-    assert!(pixels.len() == pixels.width() as usize * pixels.height() as usize* ELEMENT_BYTES);
-    let bounds = (pixels.width() as usize, pixels.height() as usize); 
+    let stride = P::CHANNEL_COUNT as usize;
+    assert!(pixels.len() == pixels.width() as usize * pixels.height() as usize * stride);
+    let bounds = (pixels.width() as usize, pixels.height() as usize);
 
     for row in 0..pixels.height() as usize {
         for column in 0..pixels.width() as usize {
             let point = pixel_to_point(bounds, (column,row), upper_left, lower_right);
-            let pixel_value = match escape_time(point,255) {
-                None => 0,
-                Some(count) => 255 - count as u8
-            };
-           
-            let pixel_color = map_color(pixel_value);
+            let mu = escape_time(point, limit, kind);
+            let pixel_color: P = map_color(mu, limit);
             pixels.put_pixel(column as u32, row as u32, pixel_color);
         }
     }
 }
 
-fn process_image(pixels:&mut [u8], 
-    bounds:(usize,usize), 
+fn process_image<P>(pixels:&mut [u8],
+    bounds:(usize,usize),
     upper_left: Complex<f64>,
-    lower_right: Complex<f64>)
+    lower_right: Complex<f64>,
+    kind: FractalKind,
+    limit: usize)
+where
+    P: Pixel<Subpixel = u8>,
 {
+    let stride = P::CHANNEL_COUNT as usize;
     let mut offset = 0;
-    for r in 0..bounds.1  { 
+    for r in 0..bounds.1  {
         offset = offset + r;
         for c in 0..bounds.0 {
             let point = pixel_to_point(bounds, (c,r), upper_left, lower_right);
-            let pixel_value = match escape_time(point,255) {
-                None => 0,
-                Some(count) => 255 - count as u8
-            };
-            let pixel_color = map_color(pixel_value);
-
-            for x in 0..ELEMENT_BYTES {
-                pixels[offset + x] = pixel_color[x];
-            }
-            offset = offset + ELEMENT_BYTES;
+            let mu = escape_time(point, limit, kind);
+            let pixel_color: P = map_color(mu, limit);
+
+            pixels[offset..offset + stride].copy_from_slice(&pixel_color.channels()[..stride]);
+            offset = offset + stride;
         }
     }
 }
@@ -201,48 +534,177 @@ fn process_image(pixels:&mut [u8],
 /// Render concurrently using multiple threads. Number of threads is determined
 /// By hardware capabilties using num_cpus
 /// To concurrently update multiple pixels, we need to work on the underlying
-/// buffer because RgbImage does not have a suitable mutable construct.
-/// These means that we need to know how many bytes per pixer are being used
-/// Currently this is a hard coded constant ELEMENT_BYTE to allow for RGB
-/// An enhancement would be to extract this info from the RgbImage and make the 
-/// calculation dynamic - MJDTODO
-fn render_multi(pixels:&mut RgbImage,
+/// buffer because ImageBuffer does not have a suitable mutable construct.
+/// Both the stride between pixels and the color conversion itself are generic
+/// over 'P', so this works unchanged for a GrayImage or RgbaImage buffer, not
+/// just RgbImage.
+fn render_multi<P>(pixels: &mut ImageBuffer<P, Vec<u8>>,
     upper_left: Complex<f64>,
-    lower_right: Complex<f64>)
+    lower_right: Complex<f64>,
+    kind: FractalKind,
+    limit: usize)
+where
+    P: Pixel<Subpixel = u8> + Send + Sync,
 {
     println!("Running multithreaded with Rayon");
     let bounds = (pixels.width() as usize, pixels.height() as usize);
     let width = pixels.width() as usize;
+    let height = pixels.height() as usize;
+    let stride = P::CHANNEL_COUNT as usize;
+    let completed = std::sync::atomic::AtomicUsize::new(0);
 
-    let bands: Vec<(usize, &mut [u8])> = 
-        pixels.chunks_mut(width * ELEMENT_BYTES)
+    let bands: Vec<(usize, &mut [u8])> =
+        pixels.chunks_mut(width * stride)
         .enumerate()
         .collect();
-    
+
     bands.into_par_iter()
         .for_each(|(i, band)| {
             let top = i;
-            let band_bounds = (bounds.0, 1); // One row 
+            let band_bounds = (bounds.0, 1); // One row
             let band_upper_left = pixel_to_point(bounds, (0,top), upper_left, lower_right);
             let band_lower_right = pixel_to_point(bounds, (bounds.0, top + 1), upper_left, lower_right);
-            process_image(band, band_bounds, band_upper_left, band_lower_right);
+            process_image::<P>(band, band_bounds, band_upper_left, band_lower_right, kind, limit);
+
+            let done = completed.fetch_add(1, Ordering::Relaxed) + 1;
+            print_progress(done, height);
         });
+    eprintln!();
+}
+
+/// Redraw a textual progress bar on stderr, e.g. `[####----] 47% (376/800 rows)`.
+fn print_progress(done: usize, total: usize) {
+    const BAR_WIDTH: usize = 40;
+    let fraction = done as f64 / total as f64;
+    let filled = (fraction * BAR_WIDTH as f64) as usize;
+    let bar: String = (0..BAR_WIDTH).map(|i| if i < filled { '#' } else { '-' }).collect();
+    eprint!("\r[{}] {:>3}% ({}/{} rows)", bar, (fraction * 100.0) as usize, done, total);
 }
 
+/// Render a Buddhabrot: instead of one escape-time sample per pixel, draw
+/// 'samples' random points from the viewport, iterate the plain Mandelbrot
+/// recurrence `z = z*z + c`, and for every orbit that escapes before 'limit'
+/// iterations, record every intermediate 'z' it visited into a histogram of
+/// per-pixel hit counts. Orbits that never escape are discarded, since it is
+/// their surrounding, escaping neighbors that trace out the Buddhabrot's
+/// ghostly silhouette.
+///
+/// The histogram is a flat `Vec<AtomicU32>` so Rayon worker threads can
+/// accumulate into it concurrently without a lock.
+fn render_buddhabrot(pixels: &mut RgbImage,
+    upper_left: Complex<f64>,
+    lower_right: Complex<f64>,
+    samples: usize,
+    limit: usize)
+{
+    println!("Running Buddhabrot with {} samples", samples);
+    let bounds = (pixels.width() as usize, pixels.height() as usize);
+    let histogram: Vec<AtomicU32> = (0..bounds.0 * bounds.1)
+        .map(|_| AtomicU32::new(0))
+        .collect();
+
+    // gen_range panics on an empty/reversed range, so sort each axis rather
+    // than assuming upper_left/lower_right are given in the usual orientation.
+    let (re_min, re_max) = (upper_left.re.min(lower_right.re), upper_left.re.max(lower_right.re));
+    let (im_min, im_max) = (upper_left.im.min(lower_right.im), upper_left.im.max(lower_right.im));
+
+    (0..samples).into_par_iter().for_each(|_| {
+        let mut rng = rand::thread_rng();
+        let c = Complex {
+            re: rng.gen_range(re_min..re_max),
+            im: rng.gen_range(im_min..im_max),
+        };
+
+        let mut orbit = Vec::new();
+        let mut z = Complex { re: 0.0, im: 0.0 };
+        let mut escaped = false;
+        for _ in 0..limit {
+            if z.norm_sqr() > 4.0 {
+                escaped = true;
+                break;
+            }
+            orbit.push(z);
+            z = z * z + c;
+        }
+
+        if escaped {
+            for point in orbit {
+                if let Some((column, row)) = point_to_pixel(bounds, point, upper_left, lower_right) {
+                    histogram[row * bounds.0 + column].fetch_add(1, Ordering::Relaxed);
+                }
+            }
+        }
+    });
+
+    let max_count = histogram.iter()
+        .map(|count| count.load(Ordering::Relaxed))
+        .max()
+        .unwrap_or(0)
+        .max(1) as f64;
+
+    for row in 0..bounds.1 {
+        for column in 0..bounds.0 {
+            let count = histogram[row * bounds.0 + column].load(Ordering::Relaxed) as f64;
+            // Orbit density spans orders of magnitude, so compress it with a
+            // log curve instead of a linear one to keep faint wisps visible.
+            let brightness = (count.ln_1p() / max_count.ln_1p() * 255.0) as u8;
+            pixels.put_pixel(column as u32, row as u32, Rgb([brightness, brightness, brightness]));
+        }
+    }
+}
 
-fn map_color(value: u8) -> image::Rgb<u8>
+/// Map a (possibly absent) normalized iteration count to a color.
+///
+/// 'mu' is normalized over the `0..limit` range and fed into a cosine-based
+/// gradient, which varies smoothly with no hard edges - unlike indexing into
+/// a small table of bands. Points that never escaped (interior points) are
+/// left black.
+fn map_color<P: Pixel<Subpixel = u8>>(mu: Option<f64>, limit: usize) -> P
 {
-    match value {
-        0 => Rgb([0,0,0]),
-        1..=35 => Rgb([148, 0, 211]),       // Violet
-        36..=70 => Rgb([75, 0, 130]),       // Indigo
-        71..=105 => Rgb([0, 0, 255]),       // Blue
-        106..=140 => Rgb([0, 255, 0]),      // Green
-        141..=175 => Rgb([255, 255, 0]),    // Yellow
-        176..=210 => Rgb([255, 127, 0]),    // Orange
-        211..=254 => Rgb([255, 0, 0]),      // Red
-        255 => Rgb([255,255,255])           // White
+    use std::f64::consts::TAU;
+
+    let rgb = match mu {
+        None => Rgb([0, 0, 0]),
+        Some(mu) => {
+            let t = mu / limit as f64;
+            let channel = |phase: f64| (127.5 * (1.0 + (TAU * t + phase).cos())) as u8;
+            Rgb([channel(0.0), channel(TAU / 3.0), channel(2.0 * TAU / 3.0)])
+        }
+    };
+    rgb_to_pixel(rgb)
+}
+
+/// Convert an RGB triplet into whatever pixel type 'P' the destination image
+/// buffer uses, so `map_color` isn't tied to `RgbImage`. Single-channel
+/// buffers (e.g. `GrayImage`) get the average of the three channels; a
+/// fourth channel (e.g. `RgbaImage`) is filled in as fully opaque.
+fn rgb_to_pixel<P: Pixel<Subpixel = u8>>(rgb: Rgb<u8>) -> P {
+    let luma = ((rgb[0] as u32 + rgb[1] as u32 + rgb[2] as u32) / 3) as u8;
+    let mut raw = [0u8; 4];
+    match P::CHANNEL_COUNT {
+        1 => raw[0] = luma,
+        2 => { raw[0] = luma; raw[1] = 255; }
+        3 => raw[..3].copy_from_slice(&rgb.0),
+        4 => { raw[..3].copy_from_slice(&rgb.0); raw[3] = 255; }
+        n => panic!("unsupported pixel channel count: {}", n),
     }
+    *P::from_slice(&raw[..P::CHANNEL_COUNT as usize])
 }
 
+#[test]
+fn test_map_color() {
+    assert_eq!(map_color::<Rgb<u8>>(None, 255), Rgb([0, 0, 0]));
+    // At mu=0 the cosines land on 0deg/120deg/240deg, giving exact values.
+    assert_eq!(map_color::<Rgb<u8>>(Some(0.0), 255), Rgb([255, 63, 63]));
+}
+
+#[test]
+fn test_rgb_to_pixel_gray() {
+    assert_eq!(rgb_to_pixel::<image::Luma<u8>>(Rgb([30, 60, 90])), image::Luma([60]));
+}
+
+#[test]
+fn test_rgb_to_pixel_rgba() {
+    assert_eq!(rgb_to_pixel::<image::Rgba<u8>>(Rgb([10, 20, 30])), image::Rgba([10, 20, 30, 255]));
+}
 